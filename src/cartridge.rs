@@ -1,5 +1,14 @@
+use crate::mbc::{Mbc, MBC2_RAM_LEN, RAM_BANK_SIZE, ROM_BANK_SIZE};
 use crate::metadata::Licensee;
-use std::{convert::TryFrom, num::Wrapping};
+use crate::patch::{self, PatchError};
+use std::{
+    convert::TryFrom,
+    fmt,
+    fs::File,
+    io::{self, Read},
+    num::Wrapping,
+    path::Path,
+};
 use thiserror::Error;
 
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -10,6 +19,12 @@ pub enum CartridgeError {
     InvalidOldLicenseeCode(u8),
     #[error("invalid new licensee code: {0}{1}")]
     InvalidNewLicenseeCode(char, char),
+    #[error("invalid ram size: expected {0} bytes, got {1}")]
+    InvalidRamSize(usize, usize),
+    #[error("rom too large: {0} bytes (max {MAX_ROM_SIZE})")]
+    RomTooLarge(usize),
+    #[error("rom too small: {0} bytes (min {MIN_ROM_SIZE})")]
+    RomTooSmall(usize),
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -82,12 +97,128 @@ impl TryFrom<u8> for Kind {
     }
 }
 
+// Memory bank controller family of a cartridge kind, ignoring its extra hardware
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MbcFamily {
+    None,
+    Mbc1,
+    Mbc2,
+    Mbc3,
+    Mbc5,
+    Mbc6,
+    Mbc7,
+    Mmm01,
+    PocketCamera,
+    BandaiTama5,
+    Huc1,
+    Huc3,
+}
+
+// Classification of the hardware each kind carries, following the names in the header table. Kinds
+// whose names don't spell out their hardware (Pocket Camera, HuC3) are classified from their docs.
+// https://gbdev.io/pandocs/The_Cartridge_Header.html#0147--cartridge-type
+impl Kind {
+    // MBC2 has no separate RAM chip but always has its built-in 512×4 bits.
+    pub fn has_ram(&self) -> bool {
+        matches!(
+            self,
+            Kind::Mb1Ram
+                | Kind::Mbc1RamBattery
+                | Kind::Mbc2
+                | Kind::Mbc2Battery
+                | Kind::RomRam
+                | Kind::RomRamBattery
+                | Kind::Mmm01Ram
+                | Kind::Mmm01RamBattery
+                | Kind::Mbc3TimerRamBattery
+                | Kind::Mbc3Ram
+                | Kind::Mbc3RamBattery
+                | Kind::Mbc5Ram
+                | Kind::Mbc5RamBattery
+                | Kind::Mbc5RumbleRam
+                | Kind::Mbc5RumbleRamBattery
+                | Kind::Mbc7SensorRumbleRamBattery
+                | Kind::PocketCamera
+                | Kind::Huc3
+                | Kind::Huc1RamBattery
+        )
+    }
+
+    pub fn has_battery(&self) -> bool {
+        matches!(
+            self,
+            Kind::Mbc1RamBattery
+                | Kind::Mbc2Battery
+                | Kind::RomRamBattery
+                | Kind::Mmm01RamBattery
+                | Kind::Mbc3TimerBattery
+                | Kind::Mbc3TimerRamBattery
+                | Kind::Mbc3RamBattery
+                | Kind::Mbc5RamBattery
+                | Kind::Mbc5RumbleRamBattery
+                | Kind::Mbc7SensorRumbleRamBattery
+                | Kind::PocketCamera
+                | Kind::Huc3
+                | Kind::Huc1RamBattery
+        )
+    }
+
+    pub fn has_rtc(&self) -> bool {
+        matches!(
+            self,
+            Kind::Mbc3TimerBattery | Kind::Mbc3TimerRamBattery | Kind::Huc3
+        )
+    }
+
+    pub fn has_rumble(&self) -> bool {
+        matches!(
+            self,
+            Kind::Mbc5Rumble
+                | Kind::Mbc5RumbleRam
+                | Kind::Mbc5RumbleRamBattery
+                | Kind::Mbc7SensorRumbleRamBattery
+        )
+    }
+
+    pub fn mbc_family(&self) -> MbcFamily {
+        match self {
+            Kind::RomOnly | Kind::RomRam | Kind::RomRamBattery => MbcFamily::None,
+            Kind::Mbc1 | Kind::Mb1Ram | Kind::Mbc1RamBattery => MbcFamily::Mbc1,
+            Kind::Mbc2 | Kind::Mbc2Battery => MbcFamily::Mbc2,
+            Kind::Mmm01 | Kind::Mmm01Ram | Kind::Mmm01RamBattery => MbcFamily::Mmm01,
+            Kind::Mbc3TimerBattery
+            | Kind::Mbc3TimerRamBattery
+            | Kind::Mbc3
+            | Kind::Mbc3Ram
+            | Kind::Mbc3RamBattery => MbcFamily::Mbc3,
+            Kind::Mbc5
+            | Kind::Mbc5Ram
+            | Kind::Mbc5RamBattery
+            | Kind::Mbc5Rumble
+            | Kind::Mbc5RumbleRam
+            | Kind::Mbc5RumbleRamBattery => MbcFamily::Mbc5,
+            Kind::Mbc6 => MbcFamily::Mbc6,
+            Kind::Mbc7SensorRumbleRamBattery => MbcFamily::Mbc7,
+            Kind::PocketCamera => MbcFamily::PocketCamera,
+            Kind::BandaiTama5 => MbcFamily::BandaiTama5,
+            Kind::Huc3 => MbcFamily::Huc3,
+            Kind::Huc1RamBattery => MbcFamily::Huc1,
+        }
+    }
+}
+
 const NINTENDO_LOGO: [u8; 48] = [
     0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
     0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
     0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
 ];
 
+// Smallest ROM that still contains the whole header ($0100–$014F)
+pub const MIN_ROM_SIZE: usize = 0x150;
+
+// Largest ROM any supported controller can address (MBC5, 512 banks × 16 KiB)
+pub const MAX_ROM_SIZE: usize = 8 * 1024 * 1024;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum ColorMode {
     None,
@@ -95,25 +226,235 @@ pub enum ColorMode {
     Required,
 }
 
+// Outcome of the boot rom's logo check
+#[derive(Debug, PartialEq, Eq)]
+pub enum LogoStatus {
+    // Boots on every model
+    Valid,
+    // Only the top half matches, so it boots on CGB and later but locks up on DMG
+    ValidCgbHalf,
+    // Locks up on every model
+    Invalid,
+}
+
+// Decoded summary of the cartridge header
+#[derive(Debug, PartialEq, Eq)]
+pub struct Header {
+    pub title: String,
+    pub kind: Result<Kind, CartridgeError>,
+    pub licensee: Result<Licensee, CartridgeError>,
+    // KiB
+    pub rom_size: Option<usize>,
+    // KiB
+    pub ram_size: usize,
+    pub color_mode: ColorMode,
+    pub header_checksum_valid: bool,
+    pub global_checksum_valid: bool,
+}
+
 // https://gbdev.io/pandocs/The_Cartridge_Header.html
 pub struct Cartridge {
     pub rom: Vec<u8>,
+    // External RAM ($A000–$BFFF), sized from the header. Empty if the cartridge has none.
+    pub ram: Vec<u8>,
+    mbc: Mbc,
 }
 
 impl Cartridge {
     pub fn new(rom: Vec<u8>) -> Self {
-        Cartridge { rom }
+        // unknown kinds fall back to no controller, and behave like a plain ROM
+        let mbc = match rom.get(0x147).map(|&kind| Kind::try_from(kind)) {
+            Some(Ok(kind)) => Mbc::new(&kind),
+            _ => Mbc::None,
+        };
+
+        let mut cart = Cartridge {
+            rom,
+            ram: vec![],
+            mbc,
+        };
+        cart.ram = vec![0x00; cart.ram_len()];
+        cart
+    }
+
+    // Like new, but rejects ROMs that can't be a real cartridge instead of trusting the buffer.
+    pub fn try_new(rom: Vec<u8>) -> Result<Self, CartridgeError> {
+        let cart = Cartridge::new(rom);
+        cart.validate_size()?;
+        Ok(cart)
+    }
+
+    // Checks the ROM holds a whole header and fits in the largest cartridge, shared by every
+    // validating constructor so they agree on what a real cartridge is.
+    pub fn validate_size(&self) -> Result<(), CartridgeError> {
+        if self.rom.len() < MIN_ROM_SIZE {
+            return Err(CartridgeError::RomTooSmall(self.rom.len()));
+        }
+
+        if self.rom.len() > MAX_ROM_SIZE {
+            return Err(CartridgeError::RomTooLarge(self.rom.len()));
+        }
+
+        Ok(())
+    }
+
+    // Reads and validates a ROM file, invalid ROMs are reported as io::ErrorKind::InvalidData.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Cartridge::from_reader(File::open(path)?)
+    }
+
+    // Reads and validates a ROM from a stream, invalid ROMs are reported as io::ErrorKind::InvalidData.
+    // At most one byte past MAX_ROM_SIZE is buffered, so an endless stream can't exhaust memory.
+    pub fn from_reader<R: Read>(reader: R) -> io::Result<Self> {
+        let mut rom = vec![];
+        reader.take(MAX_ROM_SIZE as u64 + 1).read_to_end(&mut rom)?;
+
+        Cartridge::try_new(rom).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
     }
 
     pub fn read(&self, address: u16) -> u8 {
-        self.rom[address as usize]
+        match &self.mbc {
+            Mbc::None => match address {
+                0x0000..=0x7FFF => self.read_rom(address as usize),
+                0xA000..=0xBFFF => self.read_ram(address),
+                _ => 0xFF,
+            },
+            Mbc::Mbc1(mbc) => match address {
+                0x0000..=0x3FFF => self.read_bank(mbc.zero_bank(), address),
+                0x4000..=0x7FFF => self.read_bank(mbc.rom_bank(), address),
+                0xA000..=0xBFFF => self.read_ram(address),
+                _ => 0xFF,
+            },
+            Mbc::Mbc3(mbc) => match address {
+                0x0000..=0x3FFF => self.read_rom(address as usize),
+                0x4000..=0x7FFF => self.read_bank(mbc.rom_bank(), address),
+                0xA000..=0xBFFF => mbc.read_rtc().unwrap_or_else(|| self.read_ram(address)),
+                _ => 0xFF,
+            },
+        }
     }
 
     pub fn write(&mut self, address: u16, value: u8) {
-        panic!(
-            "not implemented: write to cartridge: address: {:#06x}, value: {:#04x}",
-            address, value
-        );
+        match (address, self.ram_bank()) {
+            (0xA000..=0xBFFF, Some(bank)) => {
+                let index = self.ram_index(bank, address);
+                self.ram[index] = value;
+                return;
+            }
+            // not mapped to the cartridge
+            (0x8000..=0x9FFF | 0xC000..=0xFFFF, _) => return,
+            _ => {}
+        }
+
+        let has_controller = self.mbc_family() != MbcFamily::None;
+        match &mut self.mbc {
+            // external RAM is missing or disabled
+            Mbc::None if (0xA000..=0xBFFF).contains(&address) => {}
+            // controllers that aren't emulated yet ignore register writes
+            Mbc::None if has_controller => {}
+            Mbc::None => panic!(
+                "not implemented: write to cartridge: address: {:#06x}, value: {:#04x}",
+                address, value
+            ),
+            Mbc::Mbc1(mbc) => mbc.write(address, value),
+            Mbc::Mbc3(mbc) => mbc.write(address, value),
+        }
+    }
+
+    // Applies an IPS patch to the ROM. Checksums aren't fixed up, use is_header_checksum_valid and
+    // is_global_checksum_valid to re-validate the patched ROM.
+    // If the patch changes the cartridge kind or sizes ($0147–$0149), the controller and RAM are
+    // rebuilt from the patched header, discarding bank selection and RAM contents.
+    pub fn apply_ips(&mut self, patch: &[u8]) -> Result<(), PatchError> {
+        let hardware = self.rom.get(0x147..0x14A).map(<[u8]>::to_vec);
+        patch::apply_ips(&mut self.rom, patch)?;
+
+        if self.rom.get(0x147..0x14A).map(<[u8]>::to_vec) != hardware {
+            *self = Cartridge::new(std::mem::take(&mut self.rom));
+        }
+
+        Ok(())
+    }
+
+    // Dumps the raw external RAM banks, e.g. to persist battery-backed saves.
+    pub fn save_ram(&self) -> Vec<u8> {
+        self.ram.clone()
+    }
+
+    // Restores external RAM from a previous save_ram. The size must match the header's RAM size.
+    pub fn load_ram(&mut self, data: &[u8]) -> Result<(), CartridgeError> {
+        if data.len() != self.ram.len() {
+            return Err(CartridgeError::InvalidRamSize(self.ram.len(), data.len()));
+        }
+
+        self.ram.copy_from_slice(data);
+        Ok(())
+    }
+
+    // Whether external RAM is currently accessible. Controllers gate it behind a 0x0A write to
+    // $0000–$1FFF, while disabled reads return 0xFF and writes are dropped. Cartridges without a
+    // controller have no gate, so their RAM is enabled whenever present. Controllers that aren't
+    // emulated yet always report disabled.
+    pub fn ram_enabled(&self) -> bool {
+        match &self.mbc {
+            Mbc::None => self.mbc_family() == MbcFamily::None && !self.ram.is_empty(),
+            Mbc::Mbc1(mbc) => mbc.ram_enabled,
+            Mbc::Mbc3(mbc) => mbc.ram_enabled,
+        }
+    }
+
+    // RAM bank currently mapped at $A000–$BFFF, or None if RAM is missing, disabled or not selected.
+    fn ram_bank(&self) -> Option<usize> {
+        if self.ram.is_empty() {
+            return None;
+        }
+
+        if !self.ram_enabled() {
+            return None;
+        }
+
+        match &self.mbc {
+            Mbc::None => Some(0),
+            Mbc::Mbc1(mbc) => Some(mbc.ram_bank()),
+            Mbc::Mbc3(mbc) => mbc.ram_bank(),
+        }
+    }
+
+    // Index into external RAM, mirroring if the RAM is smaller than the selected bank.
+    fn ram_index(&self, bank: usize, address: u16) -> usize {
+        (bank * RAM_BANK_SIZE + (address as usize - 0xA000)) % self.ram.len()
+    }
+
+    // Reads external RAM, unmapped RAM reads as open bus (0xFF).
+    fn read_ram(&self, address: u16) -> u8 {
+        match self.ram_bank() {
+            Some(bank) => self.ram[self.ram_index(bank, address)],
+            None => 0xFF,
+        }
+    }
+
+    // Advances the cartridge's real time clock, if it has one. Callers can tie this to wall time.
+    pub fn advance_rtc(&mut self, seconds: u64) {
+        if let Mbc::Mbc3(mbc) = &mut self.mbc {
+            mbc.rtc.advance(seconds);
+        }
+    }
+
+    // Number of 16 KiB banks actually present in the ROM buffer
+    fn rom_banks(&self) -> usize {
+        (self.rom.len() / ROM_BANK_SIZE).max(1)
+    }
+
+    // Reads from a bank through the $0000–$3FFF or $4000–$7FFF window, wrapping banks larger than the
+    // ROM like the unused upper bank bits on hardware.
+    fn read_bank(&self, bank: usize, address: u16) -> u8 {
+        let bank = bank % self.rom_banks();
+        self.read_rom(bank * ROM_BANK_SIZE + (address as usize & (ROM_BANK_SIZE - 1)))
+    }
+
+    // Reads a raw ROM byte, ROMs shorter than the address space read as open bus (0xFF) past their end.
+    fn read_rom(&self, index: usize) -> u8 {
+        self.rom.get(index).copied().unwrap_or(0xFF)
     }
 
     // First address the boot rom jumps to after checking nintendo logo. Usually a NOP then JP $0150
@@ -121,6 +462,12 @@ impl Cartridge {
         &self.rom[0x100..0x104]
     }
 
+    // Whether the entry point decodes to a JP (0xC3) or a NOP then JP. Raw data files mistaken for ROMs
+    // usually fail this.
+    pub fn has_valid_entry(&self) -> bool {
+        matches!(self.entry_point(), [0xC3, ..] | [0x00, 0xC3, ..])
+    }
+
     // Area that is expected to contain a bitmap of the nintendo logo
     pub fn nintendo_logo(&self) -> &[u8] {
         &self.rom[0x104..0x134]
@@ -130,7 +477,19 @@ impl Cartridge {
     // Interestingly, gameboy colors and later only checks the top half of the logo.
     // https://gbdev.io/pandocs/Power_Up_Sequence.html?highlight=half#behavior
     pub fn is_logo_match(&self) -> bool {
-        self.nintendo_logo() == &NINTENDO_LOGO
+        self.nintendo_logo() == NINTENDO_LOGO
+    }
+
+    // Whether the boot rom would lock up on the logo check, on DMG (all 48 bytes) or CGB (first 24 bytes).
+    pub fn logo_status(&self) -> LogoStatus {
+        let logo = self.nintendo_logo();
+        if logo == NINTENDO_LOGO {
+            LogoStatus::Valid
+        } else if logo[..24] == NINTENDO_LOGO[..24] {
+            LogoStatus::ValidCgbHalf
+        } else {
+            LogoStatus::Invalid
+        }
     }
 
     // Title of the game in uppercase ASCII. 16 bytes (chars) max, padded with 0x00
@@ -138,18 +497,60 @@ impl Cartridge {
     // Example:
     //  color_mode: title is only 15 bytes ($0134–$0142)
     //  manufacturer_code: title is only 11 bytes ($0134–$013E)
+    // Stops at the first byte that isn't printable ASCII.
     pub fn title(&self) -> String {
-        let title = &self.rom[0x134..0x144];
-        title
+        self.title_bytes()
             .iter()
-            .take_while(|&&c| c != 0x00)
+            .take_while(|&&c| is_printable(c))
             .map(|&c| c as char)
             .collect()
     }
 
+    // Like title, but replaces bytes that aren't printable ASCII with '?' instead of stopping.
+    pub fn title_lossy(&self) -> String {
+        self.title_bytes()
+            .iter()
+            .map(|&c| if is_printable(c) { c as char } else { '?' })
+            .collect()
+    }
+
+    // End of the title area, which shrinks on later cartridges to make room for the manufacturer
+    // code ($013F–$0142) and CGB flag ($0143)
+    fn title_end(&self) -> usize {
+        if self.manufacturer_code().is_some() {
+            0x13F
+        } else if self.color_mode() != ColorMode::None {
+            0x143
+        } else {
+            0x144
+        }
+    }
+
+    // Raw title bytes up to the 0x00 padding
+    fn title_bytes(&self) -> &[u8] {
+        let title = &self.rom[0x134..self.title_end()];
+        match title.iter().position(|&c| c == 0x00) {
+            Some(len) => &title[..len],
+            None => title,
+        }
+    }
+
     // In newer cartridges, this is a 4-character manufacturer code (in uppercase ASCII)
-    pub fn manufacturer_code(&self) -> &[u8] {
-        &self.rom[0x13F..0x143]
+    // Only CGB-flagged cartridges have one, older cartridges use these bytes for the title.
+    pub fn manufacturer_code(&self) -> Option<String> {
+        if self.color_mode() == ColorMode::None {
+            return None;
+        }
+
+        let code = &self.rom[0x13F..0x143];
+        if !code
+            .iter()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            return None;
+        }
+
+        Some(code.iter().map(|&c| c as char).collect())
     }
 
     // The Color and later models use this byte to determine if the game supports color features.
@@ -162,9 +563,9 @@ impl Cartridge {
     }
 
     // This byte specifies whether the cartridge is a Super GameBoy cartridge.
-    // Technically old licensee code should be nintendo (0x01) too
+    // The SGB functions are only enabled if the old licensee code is also 0x33.
     pub fn is_super_gameboy(&self) -> bool {
-        self.rom[0x146] == 0x03
+        self.rom[0x146] == 0x03 && self.rom[0x14B] == 0x33
     }
 
     // This byte specifies the type of cartridge, can be used to determine memory bank controller.
@@ -180,17 +581,71 @@ impl Cartridge {
         }
     }
 
-    // This byte specifies the cartridge's ROM size.
-    // 32 KiB × (1 << <value>)
-    pub fn rom_size(&self) -> u8 {
-        32 * (1 << self.rom[0x148])
+    // This byte specifies the cartridge's ROM size, in KiB, or None if the value isn't a known size.
+    // 32 KiB × (1 << <value>) for $00–$08, $52–$54 are unofficial sizes only listed in some sources.
+    // https://gbdev.io/pandocs/The_Cartridge_Header.html#0148--rom-size
+    pub fn rom_size(&self) -> Option<usize> {
+        match self.rom[0x148] {
+            value @ 0x00..=0x08 => Some(32 << value),
+            0x52 => Some(1152),
+            0x53 => Some(1280),
+            0x54 => Some(1536),
+            _ => None,
+        }
     }
 
-    // This byte specifies the cartridge's RAM size.
-    pub fn ram_size(&self) -> u8 {
-        // todo(robherley): should be 0 if no ram (check kind)
-        // maybe derive number of banks from value or just from the kind
-        self.rom[0x149]
+    // This byte specifies the cartridge's external RAM size, in KiB.
+    // MBC2 has no external RAM, so this is 0 for it, see builtin_ram_len for its built-in RAM.
+    pub fn ram_size(&self) -> usize {
+        self.external_ram_len() / 1024
+    }
+
+    // Number of 8 KiB external RAM banks.
+    pub fn ram_banks(&self) -> usize {
+        self.external_ram_len() / RAM_BANK_SIZE
+    }
+
+    // Number of 4-bit cells of RAM built into the controller, which only MBC2 has.
+    pub fn builtin_ram_len(&self) -> usize {
+        if self.is_mbc2() {
+            MBC2_RAM_LEN
+        } else {
+            0
+        }
+    }
+
+    // Controller family from the header, tolerating truncated headers since new doesn't validate the rom.
+    // Unknown kinds are treated as having no controller, like new does.
+    fn mbc_family(&self) -> MbcFamily {
+        self.rom
+            .get(0x147)
+            .and_then(|&byte| Kind::try_from(byte).ok())
+            .map_or(MbcFamily::None, |kind| kind.mbc_family())
+    }
+
+    fn is_mbc2(&self) -> bool {
+        self.mbc_family() == MbcFamily::Mbc2
+    }
+
+    // Size of external RAM in bytes, decoded from the header.
+    // https://gbdev.io/pandocs/The_Cartridge_Header.html#0149--ram-size
+    fn external_ram_len(&self) -> usize {
+        if self.is_mbc2() {
+            return 0;
+        }
+
+        match self.rom.get(0x149) {
+            Some(0x02) => 8 * 1024,
+            Some(0x03) => 32 * 1024,
+            Some(0x04) => 128 * 1024,
+            Some(0x05) => 64 * 1024,
+            _ => 0,
+        }
+    }
+
+    // Size of the RAM buffer backing $A000–$BFFF, external or built-in.
+    fn ram_len(&self) -> usize {
+        self.external_ram_len() + self.builtin_ram_len()
     }
 
     // This byte specifies whether this version of the game is intended to be sold in Japan or elsewhere.
@@ -224,23 +679,116 @@ impl Cartridge {
         u16::from_be_bytes([self.rom[0x14E], self.rom[0x14F]])
     }
 
-    // Computes the header checksum to see if it's valid. This is _NOT_ verified by the boot ROM.
-    pub fn is_global_checksum_valid(&self) -> bool {
-        let checksum = self
-            .rom
+    // Computes the global checksum: the wrapping 16-bit sum of every ROM byte except the checksum itself.
+    pub fn computed_global_checksum(&self) -> u16 {
+        self.rom
             .iter()
             .enumerate()
             .map(|(i, &b)| {
-                match i as u16 {
-                    // skip global checksum bytes
+                // only skip the global checksum bytes in the header, not their offsets in other banks
+                match i {
                     0x14E | 0x14F => Wrapping(0),
                     _ => Wrapping(b as u16),
                 }
             })
             .sum::<Wrapping<u16>>()
-            .0;
+            .0
+    }
+
+    // Computes the global checksum to see if it's valid. This is _NOT_ verified by the boot ROM.
+    pub fn is_global_checksum_valid(&self) -> bool {
+        self.computed_global_checksum() == self.global_checksum()
+    }
 
-        checksum == self.global_checksum()
+    // Bundles the decoded header fields, e.g. for tools that want to inspect a ROM.
+    pub fn header(&self) -> Header {
+        Header {
+            title: self.title(),
+            kind: self.kind(),
+            licensee: self.licensee(),
+            rom_size: self.rom_size(),
+            ram_size: self.ram_size(),
+            color_mode: self.color_mode(),
+            header_checksum_valid: self.is_header_checksum_valid(),
+            global_checksum_valid: self.is_global_checksum_valid(),
+        }
+    }
+
+    // Header fields holding values that no known encoding accounts for, as (address, value).
+    // Useful for flagging non-standard or tampered headers. Only these fields are checked:
+    //  - title padding after the first 0x00 that isn't 0x00 (up to $013E, $0142 or $0143 depending
+    //    on how much of the title area the cartridge uses)
+    //  - new licensee code set without the 0x33 old licensee marker ($0144–$0145)
+    //  - SGB flag other than 0x00/0x03 ($0146)
+    //  - destination code other than 0x00/0x01 ($014A)
+    pub fn reserved_bytes(&self) -> Vec<(u16, u8)> {
+        let mut reserved = vec![];
+
+        let title = &self.rom[0x134..self.title_end()];
+        if let Some(end) = title.iter().position(|&c| c == 0x00) {
+            for (i, &b) in title.iter().enumerate().skip(end) {
+                if b != 0x00 {
+                    reserved.push((0x134 + i as u16, b));
+                }
+            }
+        }
+
+        if self.rom[0x14B] != 0x33 {
+            for addr in [0x144, 0x145] {
+                if self.rom[addr] != 0x00 {
+                    reserved.push((addr as u16, self.rom[addr]));
+                }
+            }
+        }
+
+        if !matches!(self.rom[0x146], 0x00 | 0x03) {
+            reserved.push((0x146, self.rom[0x146]));
+        }
+
+        if !matches!(self.rom[0x14A], 0x00 | 0x01) {
+            reserved.push((0x14A, self.rom[0x14A]));
+        }
+
+        reserved
+    }
+}
+
+fn is_printable(c: u8) -> bool {
+    c.is_ascii_graphic() || c == b' '
+}
+
+impl fmt::Display for Cartridge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = self.header();
+        let validity = |valid: bool| if valid { "valid" } else { "invalid" };
+
+        writeln!(f, "title: {}", header.title)?;
+        match header.kind {
+            Ok(kind) => writeln!(f, "kind: {:?}", kind)?,
+            Err(err) => writeln!(f, "kind: {}", err)?,
+        }
+        match header.licensee {
+            Ok(licensee) => writeln!(f, "licensee: {:?}", licensee)?,
+            Err(err) => writeln!(f, "licensee: {}", err)?,
+        }
+        match header.rom_size {
+            Some(size) => writeln!(f, "rom size: {} KiB", size)?,
+            None => writeln!(f, "rom size: unknown ({:#04x})", self.rom[0x148])?,
+        }
+        writeln!(f, "ram size: {} KiB", header.ram_size)?;
+        writeln!(f, "color mode: {:?}", header.color_mode)?;
+        writeln!(
+            f,
+            "header checksum: {:#04X} ({})",
+            self.header_checksum(),
+            validity(header.header_checksum_valid)
+        )?;
+        write!(
+            f,
+            "global checksum: {:#06X} ({})",
+            self.global_checksum(),
+            validity(header.global_checksum_valid)
+        )
     }
 }
 
@@ -260,6 +808,37 @@ mod tests {
         assert!(cart.is_logo_match(), "matches nintendo logo");
     }
 
+    #[test]
+    fn test_logo_status() {
+        let mut rom = CPU_INSTRS_ROM.to_vec();
+        assert_eq!(Cartridge::new(rom.clone()).logo_status(), LogoStatus::Valid);
+
+        rom[0x104 + 24] ^= 0xFF;
+        let cart = Cartridge::new(rom.clone());
+        assert_eq!(
+            cart.logo_status(),
+            LogoStatus::ValidCgbHalf,
+            "bottom half differs"
+        );
+        assert!(!cart.is_logo_match());
+
+        rom[0x104] ^= 0xFF;
+        assert_eq!(Cartridge::new(rom).logo_status(), LogoStatus::Invalid);
+    }
+
+    #[test]
+    fn test_has_valid_entry() {
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        assert!(cart.has_valid_entry(), "nop then jp");
+
+        let mut rom = vec![0x00; 65536];
+        rom[0x100] = 0xC3;
+        assert!(Cartridge::new(rom).has_valid_entry(), "jp");
+
+        let cart = Cartridge::new(vec![0x00; 65536]);
+        assert!(!cart.has_valid_entry(), "zero filled");
+    }
+
     #[test]
     fn test_kind() {
         let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
@@ -280,6 +859,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_kind_predicates() {
+        assert!(!Kind::RomOnly.has_ram());
+        assert!(Kind::Mbc1RamBattery.has_ram());
+        assert!(Kind::Mbc2.has_ram(), "mbc2 has built-in ram");
+        assert!(!Kind::Mbc3TimerBattery.has_ram());
+
+        assert!(!Kind::Mb1Ram.has_battery());
+        assert!(Kind::Mbc1RamBattery.has_battery());
+        assert!(Kind::Mbc3TimerBattery.has_battery());
+
+        assert!(Kind::Mbc3TimerRamBattery.has_rtc());
+        assert!(!Kind::Mbc3RamBattery.has_rtc());
+
+        assert!(Kind::Mbc5Rumble.has_rumble());
+        assert!(Kind::Mbc7SensorRumbleRamBattery.has_rumble());
+        assert!(!Kind::Mbc5RamBattery.has_rumble());
+
+        assert_eq!(Kind::RomRam.mbc_family(), MbcFamily::None);
+        assert_eq!(Kind::Mb1Ram.mbc_family(), MbcFamily::Mbc1);
+        assert_eq!(Kind::Mbc3TimerBattery.mbc_family(), MbcFamily::Mbc3);
+        assert_eq!(Kind::Mbc5RumbleRam.mbc_family(), MbcFamily::Mbc5);
+        assert_eq!(Kind::Huc1RamBattery.mbc_family(), MbcFamily::Huc1);
+
+        // battery-backed 128 KiB of RAM
+        assert!(Kind::PocketCamera.has_ram());
+        assert!(Kind::PocketCamera.has_battery());
+        assert!(!Kind::PocketCamera.has_rtc());
+
+        // battery-backed RAM and an RTC
+        assert!(Kind::Huc3.has_ram());
+        assert!(Kind::Huc3.has_battery());
+        assert!(Kind::Huc3.has_rtc());
+    }
+
     #[test]
     fn test_licensee() {
         let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
@@ -310,8 +924,8 @@ mod tests {
 
         let mut rom = vec![0x00; 65536];
         rom[0x14B] = 0x33;
-        rom[0x144] = '6' as u8;
-        rom[0x145] = '9' as u8;
+        rom[0x144] = b'6';
+        rom[0x145] = b'9';
         assert_eq!(
             Cartridge::new(rom).licensee().unwrap(),
             Licensee::New("Electronic Arts"),
@@ -320,8 +934,8 @@ mod tests {
 
         let mut rom = vec![0x00; 65536];
         rom[0x14B] = 0x33;
-        rom[0x144] = 'Z' as u8;
-        rom[0x145] = 'Z' as u8;
+        rom[0x144] = b'Z';
+        rom[0x145] = b'Z';
         assert_eq!(
             Cartridge::new(rom).licensee().unwrap_err(),
             CartridgeError::InvalidNewLicenseeCode('Z', 'Z'),
@@ -329,22 +943,133 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_title_printable() {
+        let mut rom = vec![0x00; 65536];
+        rom[0x134..0x13C].copy_from_slice(b"MY GAME!");
+        rom[0x137] = 0x80;
+        let cart = Cartridge::new(rom.clone());
+        assert_eq!(cart.title(), "MY ", "truncated at 0x80");
+        assert_eq!(cart.title_lossy(), "MY ?AME!");
+
+        rom[0x137] = 0x07;
+        let cart = Cartridge::new(rom);
+        assert_eq!(cart.title(), "MY ", "truncated at control character");
+        assert_eq!(cart.title_lossy(), "MY ?AME!");
+
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        assert_eq!(cart.title_lossy(), "CPU_INSTRS");
+    }
+
+    #[test]
+    fn test_cgb_title() {
+        let mut rom = vec![0x00; 65536];
+        rom[0x134..0x144].copy_from_slice(b"ABCDEFGHIJKLMNOP");
+        let cart = Cartridge::new(rom.clone());
+        assert_eq!(cart.title(), "ABCDEFGHIJKLMNOP", "dmg title is 16 bytes");
+        assert_eq!(
+            cart.manufacturer_code(),
+            None,
+            "dmg has no manufacturer code"
+        );
+
+        rom[0x13F..0x143].copy_from_slice(b"abc!");
+        rom[0x143] = 0x80;
+        let cart = Cartridge::new(rom.clone());
+        assert_eq!(cart.title(), "ABCDEFGHIJKabc!", "cgb title is 15 bytes");
+        assert_eq!(cart.manufacturer_code(), None, "invalid manufacturer code");
+
+        rom[0x13F..0x143].copy_from_slice(b"AXV1");
+        rom[0x143] = 0xC0;
+        let cart = Cartridge::new(rom);
+        assert_eq!(cart.title(), "ABCDEFGHIJK", "title is 11 bytes");
+        assert_eq!(cart.manufacturer_code(), Some("AXV1".to_string()));
+    }
+
     #[test]
     fn test_color_mode() {
         let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
         assert_eq!(cart.color_mode(), ColorMode::Supports);
     }
 
+    #[test]
+    fn test_is_super_gameboy() {
+        let mut rom = CPU_INSTRS_ROM.to_vec();
+        assert!(!Cartridge::new(rom.clone()).is_super_gameboy());
+
+        rom[0x146] = 0x03;
+        rom[0x14B] = 0x01;
+        assert!(
+            !Cartridge::new(rom.clone()).is_super_gameboy(),
+            "sgb flag without the new licensee marker"
+        );
+
+        rom[0x14B] = 0x33;
+        assert!(Cartridge::new(rom.clone()).is_super_gameboy());
+
+        rom[0x146] = 0x00;
+        assert!(!Cartridge::new(rom).is_super_gameboy());
+    }
+
     #[test]
     fn test_rom_size() {
         let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
-        assert_eq!(cart.rom_size(), 64);
+        assert_eq!(cart.rom_size(), Some(64));
+
+        for (value, size) in [
+            (0x00, Some(32)),
+            (0x08, Some(8 * 1024)),
+            (0x52, Some(1152)),
+            (0x54, Some(1536)),
+            (0x09, None),
+            (0x3B, None),
+            (0xFF, None),
+        ] {
+            let mut rom = CPU_INSTRS_ROM.to_vec();
+            rom[0x148] = value;
+            let cart = Cartridge::try_new(rom).unwrap();
+            assert_eq!(cart.rom_size(), size, "rom size for {:#04x}", value);
+            assert_eq!(cart.header().rom_size, size);
+        }
+
+        let mut rom = CPU_INSTRS_ROM.to_vec();
+        rom[0x148] = 0xFF;
+        let cart = Cartridge::try_new(rom).unwrap();
+        assert!(cart.to_string().contains("rom size: unknown (0xff)"));
     }
 
     #[test]
     fn test_ram_size() {
         let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
         assert_eq!(cart.ram_size(), 0);
+        assert_eq!(cart.ram_banks(), 0);
+
+        for (value, size, banks) in [
+            (0x00, 0, 0),
+            (0x01, 0, 0),
+            (0x02, 8, 1),
+            (0x03, 32, 4),
+            (0x04, 128, 16),
+            (0x05, 64, 8),
+        ] {
+            let mut rom = vec![0x00; 65536];
+            rom[0x149] = value;
+            let cart = Cartridge::new(rom);
+            assert_eq!(cart.ram_size(), size, "ram size for {:#04x}", value);
+            assert_eq!(cart.ram_banks(), banks, "ram banks for {:#04x}", value);
+            assert_eq!(cart.ram.len(), size * 1024);
+        }
+
+        let mut rom = vec![0x00; 65536];
+        rom[0x147] = 0x06;
+        rom[0x149] = 0x03;
+        let cart = Cartridge::new(rom);
+        assert_eq!(cart.ram_size(), 0, "mbc2 ignores the header");
+        assert_eq!(cart.ram_banks(), 0);
+        assert_eq!(cart.builtin_ram_len(), 512);
+        assert_eq!(cart.ram.len(), 512);
+        assert!(cart.to_string().contains("ram size: 0 KiB"));
+        assert_eq!(Cartridge::new(CPU_INSTRS_ROM.to_vec()).builtin_ram_len(), 0);
     }
 
     #[test]
@@ -374,5 +1099,426 @@ mod tests {
         let cart = Cartridge::new(rom);
         assert_eq!(cart.global_checksum(), 0xFFFE);
         assert!(cart.is_global_checksum_valid());
+
+        // checksum bytes are only skipped in the header, not in every 64 KiB
+        let mut rom = vec![0x00; 0x20000];
+        rom[0x1014E] = 0x01;
+        rom[0x1014F] = 0x02;
+        rom[0x14F] = 0x03;
+        let cart = Cartridge::new(rom);
+        assert_eq!(cart.computed_global_checksum(), 0x0003);
+        assert!(cart.is_global_checksum_valid());
+    }
+
+    #[test]
+    fn test_global_checksum_real_rom() {
+        // blargg's cpu_instrs ships with a stale global checksum, the boot ROM never checks it
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        assert_eq!(cart.global_checksum(), 0xF530);
+        assert_eq!(cart.computed_global_checksum(), 0xB171);
+        assert!(!cart.is_global_checksum_valid());
+
+        let mut rom = CPU_INSTRS_ROM.to_vec();
+        rom[0x14E..0x150].copy_from_slice(&0xB171u16.to_be_bytes());
+        let cart = Cartridge::new(rom);
+        assert!(cart.is_global_checksum_valid(), "stored big-endian");
+    }
+
+    #[test]
+    fn test_reserved_bytes() {
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        assert_eq!(cart.reserved_bytes(), vec![], "cpu_instrs is standard");
+
+        let mut rom = vec![0x00; 65536];
+        rom[0x134..0x138].copy_from_slice(b"TEST");
+        rom[0x13A] = 0x7F;
+        rom[0x145] = 0x01;
+        rom[0x146] = 0x02;
+        rom[0x14A] = 0x02;
+        assert_eq!(
+            Cartridge::new(rom).reserved_bytes(),
+            vec![(0x13A, 0x7F), (0x145, 0x01), (0x146, 0x02), (0x14A, 0x02)]
+        );
+
+        let mut rom = vec![0x00; 65536];
+        rom[0x14B] = 0x33;
+        rom[0x144] = b'0';
+        rom[0x145] = b'1';
+        assert_eq!(
+            Cartridge::new(rom).reserved_bytes(),
+            vec![],
+            "new licensee is in use"
+        );
+
+        let mut rom = vec![0x00; 65536];
+        rom[0x134..0x138].copy_from_slice(b"TEST");
+        rom[0x141] = 0x01;
+        assert_eq!(
+            Cartridge::new(rom.clone()).reserved_bytes(),
+            vec![(0x141, 0x01)],
+            "dmg title runs to $0143"
+        );
+
+        rom[0x143] = 0x80;
+        rom[0x13F..0x143].copy_from_slice(b"AXV1");
+        assert_eq!(
+            Cartridge::new(rom).reserved_bytes(),
+            vec![],
+            "manufacturer code isn't padding"
+        );
+    }
+
+    // builds an MBC1 rom where the first byte of every bank is the bank number
+    fn mbc1_rom(banks: usize) -> Vec<u8> {
+        let mut rom = vec![0x00; banks * ROM_BANK_SIZE];
+        for bank in 0..banks {
+            rom[bank * ROM_BANK_SIZE] = bank as u8;
+        }
+        rom[0x147] = 0x01;
+        rom
+    }
+
+    #[test]
+    fn test_mbc1_banking() {
+        let mut cart = Cartridge::new(mbc1_rom(8));
+        assert_eq!(cart.read(0x0000), 0, "bank 0 is fixed");
+        assert_eq!(cart.read(0x4000), 1, "defaults to bank 1");
+
+        cart.write(0x2000, 0x05);
+        assert_eq!(cart.read(0x4000), 5);
+        assert_eq!(cart.read(0x0000), 0, "bank 0 is unaffected");
+
+        cart.write(0x2000, 0x00);
+        assert_eq!(cart.read(0x4000), 1, "bank 0 maps to bank 1");
+
+        cart.write(0x2000, 0x0A);
+        assert_eq!(cart.read(0x4000), 2, "bank number is masked to rom size");
+    }
+
+    #[test]
+    fn test_mbc1_upper_bank_bits() {
+        let mut cart = Cartridge::new(mbc1_rom(128));
+
+        cart.write(0x4000, 0x01);
+        cart.write(0x2000, 0x03);
+        assert_eq!(cart.read(0x4000), 0x23);
+
+        for (bank2, expected) in [(1, 0x21), (2, 0x41), (3, 0x61)] {
+            cart.write(0x4000, bank2);
+            cart.write(0x2000, 0x00);
+            assert_eq!(
+                cart.read(0x4000),
+                expected,
+                "bank {:#04x} quirk",
+                bank2 << 5
+            );
+        }
+    }
+
+    #[test]
+    fn test_mbc1_mode1_zero_bank() {
+        let mut cart = Cartridge::new(mbc1_rom(64));
+        cart.write(0x4000, 0x01);
+        assert_eq!(cart.read(0x0000), 0x00, "mode 0 always reads bank 0");
+
+        cart.write(0x6000, 0x01);
+        assert_eq!(cart.read(0x0000), 0x20, "mode 1 applies upper bits");
+        assert_eq!(cart.read(0x4000), 0x21);
+
+        let mut cart = Cartridge::new(mbc1_rom(32));
+        cart.write(0x4000, 0x01);
+        cart.write(0x6000, 0x01);
+        assert_eq!(cart.read(0x0000), 0x00, "512 KiB roms ignore upper bits");
+    }
+
+    #[test]
+    fn test_rom_only_read() {
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        assert_eq!(cart.read(0x4000), CPU_INSTRS_ROM[0x4000]);
+
+        let mut rom = vec![0x00; 0x8000];
+        rom[0x7FFF] = 0x42;
+        assert_eq!(Cartridge::new(rom).read(0x7FFF), 0x42);
+    }
+
+    #[test]
+    fn test_mbc3() {
+        let mut rom = mbc1_rom(128);
+        rom[0x147] = 0x10;
+        let mut cart = Cartridge::new(rom);
+
+        cart.write(0x2000, 0x45);
+        assert_eq!(cart.read(0x4000), 0x45, "7-bit bank");
+
+        cart.write(0x0000, 0x0A);
+        cart.write(0x4000, 0x09);
+        cart.advance_rtc(60 * 5 + 7);
+        cart.write(0x6000, 0x00);
+        cart.write(0x6000, 0x01);
+        assert_eq!(cart.read(0xA000), 5, "latched minutes");
+
+        cart.write(0xA000, 30);
+        cart.write(0x6000, 0x00);
+        cart.write(0x6000, 0x01);
+        assert_eq!(cart.read(0xA000), 30, "written minutes");
+    }
+
+    #[test]
+    fn test_ram_enabled() {
+        let mut rom = mbc1_rom(8);
+        rom[0x147] = 0x02;
+        rom[0x149] = 0x02;
+        let mut cart = Cartridge::new(rom);
+        assert!(!cart.ram_enabled());
+        assert_eq!(cart.read(0xA000), 0xFF, "disabled reads return 0xFF");
+
+        cart.write(0xA000, 0x42);
+        cart.write(0x0000, 0x0A);
+        assert!(cart.ram_enabled());
+        assert_eq!(cart.read(0xA000), 0x00, "disabled writes are dropped");
+
+        cart.write(0xA000, 0x42);
+        assert_eq!(cart.read(0xA000), 0x42);
+
+        cart.write(0x0000, 0x00);
+        assert!(!cart.ram_enabled());
+        assert_eq!(cart.read(0xA000), 0xFF);
+
+        let mut rom = mbc1_rom(8);
+        rom[0x147] = 0x08;
+        rom[0x149] = 0x02;
+        assert!(Cartridge::new(rom).ram_enabled(), "rom+ram has no gate");
+        assert!(!Cartridge::new(mbc1_rom(8)).ram_enabled(), "no ram");
+
+        // MBC5+RAM and MBC2 have a gate but aren't emulated yet
+        for kind in [0x1A, 0x06] {
+            let mut rom = mbc1_rom(8);
+            rom[0x147] = kind;
+            rom[0x149] = 0x02;
+            let mut cart = Cartridge::new(rom);
+            assert!(!cart.ram.is_empty());
+            assert!(!cart.ram_enabled(), "kind {:#04x}", kind);
+            cart.write(0xA000, 0x42);
+            assert_eq!(cart.read(0xA000), 0xFF, "kind {:#04x}", kind);
+            assert_eq!(cart.ram[0], 0x00, "kind {:#04x}", kind);
+        }
+
+        let mut rom = mbc1_rom(8);
+        rom[0x147] = 0x19;
+        let mut cart = Cartridge::new(rom);
+        cart.write(0x2000, 0x02);
+        cart.write(0x0000, 0x0A);
+        assert_eq!(cart.read(0x4000), 1, "mbc5 register writes are ignored");
+    }
+
+    #[test]
+    fn test_unmapped_addresses() {
+        let mut rom = mbc1_rom(8);
+        rom[0x147] = 0x03;
+        rom[0x149] = 0x02;
+        let mut cart = Cartridge::new(rom);
+        cart.write(0x0000, 0x0A);
+
+        for address in [0x8000, 0x9FFF, 0xC000, 0xFFFF] {
+            assert_eq!(cart.read(address), 0xFF, "{:#06x}", address);
+            cart.write(address, 0x05);
+        }
+        assert!(cart.ram_enabled());
+        assert_eq!(
+            cart.read(0x4000),
+            1,
+            "writes outside the cartridge don't reach the mbc"
+        );
+
+        let mut rom = mbc1_rom(8);
+        rom[0x147] = 0x13;
+        rom[0x149] = 0x02;
+        let mut cart = Cartridge::new(rom);
+        cart.write(0x0000, 0x0A);
+        assert_eq!(cart.read(0x8000), 0xFF, "mbc3");
+        cart.write(0xC000, 0x05);
+    }
+
+    #[test]
+    fn test_ram_persistence() {
+        let mut rom = mbc1_rom(8);
+        rom[0x147] = 0x03;
+        rom[0x149] = 0x03;
+        let mut cart = Cartridge::new(rom.clone());
+        assert_eq!(cart.ram.len(), 32 * 1024);
+
+        assert_eq!(cart.read(0xA000), 0xFF, "ram is disabled");
+        cart.write(0x0000, 0x0A);
+        cart.write(0x6000, 0x01);
+        cart.write(0x4000, 0x02);
+        cart.write(0xA123, 0x42);
+        assert_eq!(cart.read(0xA123), 0x42);
+
+        let save = cart.save_ram();
+        assert_eq!(save[2 * RAM_BANK_SIZE + 0x123], 0x42, "written to bank 2");
+
+        let mut cart = Cartridge::new(rom);
+        cart.load_ram(&save).unwrap();
+        cart.write(0x0000, 0x0A);
+        cart.write(0x6000, 0x01);
+        cart.write(0x4000, 0x02);
+        assert_eq!(cart.read(0xA123), 0x42, "restored from save");
+
+        assert_eq!(
+            cart.load_ram(&[0x00; 16]).unwrap_err(),
+            CartridgeError::InvalidRamSize(32 * 1024, 16)
+        );
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(Cartridge::try_new(CPU_INSTRS_ROM.to_vec()).is_ok());
+        assert!(Cartridge::try_new(vec![0x00; MIN_ROM_SIZE]).is_ok());
+        assert!(Cartridge::try_new(vec![0x00; MAX_ROM_SIZE]).is_ok());
+        assert_eq!(
+            Cartridge::try_new(vec![0x00; 0x14F]).err(),
+            Some(CartridgeError::RomTooSmall(0x14F))
+        );
+        assert_eq!(
+            Cartridge::try_new(vec![]).err(),
+            Some(CartridgeError::RomTooSmall(0))
+        );
+        assert_eq!(
+            Cartridge::try_new(vec![0x00; 16 * 1024 * 1024]).err(),
+            Some(CartridgeError::RomTooLarge(16 * 1024 * 1024))
+        );
+    }
+
+    #[test]
+    fn test_read_short_rom() {
+        let cart = Cartridge::try_new(vec![0x00; MIN_ROM_SIZE]).unwrap();
+        assert_eq!(cart.read(0x0100), 0x00);
+        assert_eq!(cart.read(0x0200), 0xFF, "past the end of the rom");
+        assert_eq!(cart.read(0x7FFF), 0xFF);
+
+        for kind in [0x01, 0x11] {
+            let mut rom = vec![0x00; MIN_ROM_SIZE];
+            rom[0x147] = kind;
+            let cart = Cartridge::try_new(rom).unwrap();
+            assert_eq!(cart.read(0x0200), 0xFF, "kind {:#04x}", kind);
+            assert_eq!(cart.read(0x4200), 0xFF, "kind {:#04x}", kind);
+        }
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let cart = Cartridge::from_reader(io::Cursor::new(CPU_INSTRS_ROM)).unwrap();
+        assert_eq!(cart.rom, CPU_INSTRS_ROM.to_vec());
+        assert_eq!(cart.title(), "CPU_INSTRS");
+
+        let err = Cartridge::from_reader(io::repeat(0x00)).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData, "endless stream");
+    }
+
+    #[test]
+    fn test_from_path() {
+        let cart = Cartridge::from_path("test/fixtures/cpu_instrs.gb").unwrap();
+        assert_eq!(cart.title(), "CPU_INSTRS");
+
+        let err = Cartridge::from_path("test/fixtures/README.md")
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData, "too small");
+
+        let err = Cartridge::from_path("test/fixtures/missing.gb")
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_apply_ips() {
+        let mut cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        let patch = [
+            b"PATCH".as_slice(),
+            &[0x00, 0x01, 0x34, 0x00, 0x05],
+            b"HELLO",
+            b"EOF",
+        ]
+        .concat();
+
+        cart.apply_ips(&patch).unwrap();
+        assert_eq!(cart.title(), "HELLONSTRS");
+        assert!(
+            !cart.is_header_checksum_valid(),
+            "checksums aren't fixed up"
+        );
+    }
+
+    #[test]
+    fn test_apply_ips_rebuilds_mbc() {
+        let mut rom = mbc1_rom(2);
+        rom[0x147] = 0x00;
+        let mut cart = Cartridge::new(rom);
+        assert_eq!(cart.ram.len(), 0);
+
+        // MBC1+RAM+BATTERY with 8 KiB of RAM
+        let patch = [
+            b"PATCH".as_slice(),
+            &[0x00, 0x01, 0x47, 0x00, 0x03, 0x03, 0x00, 0x02],
+            b"EOF",
+        ]
+        .concat();
+        cart.apply_ips(&patch).unwrap();
+        assert_eq!(cart.kind(), Ok(Kind::Mbc1RamBattery));
+        assert_eq!(cart.ram_size(), 8);
+        assert_eq!(cart.ram.len(), 8 * 1024);
+        cart.load_ram(&[0x00; 8 * 1024]).unwrap();
+
+        cart.write(0x2000, 0x01);
+        assert_eq!(cart.read(0x4000), 1, "banking goes through the new mbc");
+        cart.write(0x0000, 0x0A);
+        cart.write(0xA000, 0x42);
+        assert_eq!(cart.read(0xA000), 0x42);
+
+        let patch = [b"PATCH".as_slice(), b"EOF", &[0x00, 0x00, 0x10]].concat();
+        assert_eq!(cart.apply_ips(&patch), Err(PatchError::TooSmall(0x10)));
+        assert_eq!(cart.title(), "", "rom is untouched on error");
+    }
+
+    #[test]
+    fn test_header() {
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        assert_eq!(
+            cart.header(),
+            Header {
+                title: "CPU_INSTRS".to_string(),
+                kind: Ok(Kind::Mbc1),
+                licensee: Ok(Licensee::None),
+                rom_size: Some(64),
+                ram_size: 0,
+                color_mode: ColorMode::Supports,
+                header_checksum_valid: true,
+                global_checksum_valid: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        assert_eq!(
+            cart.to_string(),
+            "title: CPU_INSTRS\n\
+             kind: Mbc1\n\
+             licensee: None\n\
+             rom size: 64 KiB\n\
+             ram size: 0 KiB\n\
+             color mode: Supports\n\
+             header checksum: 0x3B (valid)\n\
+             global checksum: 0xF530 (invalid)"
+        );
+
+        let mut rom = vec![0x00; 65536];
+        rom[0x147] = 0xEE;
+        rom[0x148] = 0x05;
+        let summary = Cartridge::new(rom).to_string();
+        assert!(summary.contains("kind: invalid cartridge kind: 0xee"));
+        assert!(summary.contains("rom size: 1024 KiB"));
     }
 }
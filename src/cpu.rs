@@ -1,7 +1,19 @@
-use crate::cartridge::Cartridge;
+use crate::cartridge::{Cartridge, CartridgeError, ColorMode};
+use crate::disasm;
 use crate::registers::Registers;
+use thiserror::Error;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("cpu not supported: {0:?}")]
+    CPUNotSupported(Model),
+    #[error("cartridge requires a color model: {0:?}")]
+    ColorRequired(Model),
+    #[error(transparent)]
+    Cartridge(#[from] CartridgeError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Model {
     // Original Game Boy
     DMG,
@@ -18,16 +30,169 @@ pub enum Model {
 }
 
 pub struct CPU {
+    pub model: Model,
     pub registers: Registers,
     pub cartridge: Cartridge,
 }
 
 impl CPU {
-    pub fn new(model: Model, cart: Cartridge) -> CPU {
-        CPU {
+    pub fn new(model: Model, cart: Cartridge) -> Result<CPU, Error> {
+        if !matches!(model, Model::DMG | Model::MGB | Model::CGB) {
+            return Err(Error::CPUNotSupported(model));
+        }
+
+        Ok(CPU {
+            model,
             registers: Registers::new(model, &cart),
             // TODO(robherley): mmu
             cartridge: cart,
+        })
+    }
+
+    // Like new, but validates the cartridge header up front so junk ROMs fail here instead of on the
+    // first bad memory access.
+    pub fn try_new(model: Model, cart: Cartridge) -> Result<CPU, Error> {
+        cart.validate_size()?;
+        cart.kind()?;
+
+        if cart.color_mode() == ColorMode::Required && model != Model::CGB {
+            return Err(Error::ColorRequired(model));
+        }
+
+        CPU::new(model, cart)
+    }
+
+    // Restores the power-up state without rebuilding the CPU. The cartridge, including its
+    // battery-backed RAM and bank selection, is left untouched.
+    pub fn reset(&mut self) {
+        self.registers = Registers::new(self.model, &self.cartridge);
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    // Disassembles the instruction at pc without executing it. Only the cartridge is mapped so far,
+    // so anything outside it reads as 0xFF.
+    pub fn disassemble_at(&self, pc: u16) -> String {
+        let bytes: Vec<u8> = (0..3)
+            .map(|i| self.cartridge.read(pc.wrapping_add(i)))
+            .collect();
+        disasm::decode(&bytes).0.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::MAX_ROM_SIZE;
+    use crate::registers::Flags;
+
+    const CPU_INSTRS_ROM: &[u8; 65536] = include_bytes!("../test/fixtures/cpu_instrs.gb");
+
+    #[test]
+    fn test_registers() {
+        let mut cpu = CPU::new(Model::DMG, Cartridge::new(CPU_INSTRS_ROM.to_vec())).unwrap();
+        assert_eq!(cpu.registers().pc, 0x0100);
+        assert_eq!(cpu.registers().sp, 0xFFFE);
+        assert!(cpu.registers().get_flag(Flags::Z));
+
+        cpu.registers_mut().set_hl(0xBEEF);
+        assert_eq!(cpu.registers().hl(), 0xBEEF);
+        assert_eq!(cpu.registers().h, 0xBE);
+    }
+
+    #[test]
+    fn test_disassemble_at() {
+        let cpu = CPU::new(Model::DMG, Cartridge::new(CPU_INSTRS_ROM.to_vec())).unwrap();
+        assert_eq!(cpu.disassemble_at(0x0100), "NOP");
+        assert_eq!(cpu.disassemble_at(0x0101), "JP $0637");
+        assert_eq!(
+            cpu.disassemble_at(0xFFFF),
+            "RST $38",
+            "wraps and reads unmapped memory as 0xFF"
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut rom = CPU_INSTRS_ROM.to_vec();
+        rom[0x147] = 0x03;
+        rom[0x149] = 0x02;
+        let mut cpu = CPU::new(Model::DMG, Cartridge::new(rom)).unwrap();
+        let initial = Registers::new(Model::DMG, &cpu.cartridge);
+
+        cpu.registers_mut().a = 0x42;
+        cpu.registers_mut().pc = 0xC000;
+        cpu.registers_mut().set_flag(Flags::Z, false);
+        cpu.cartridge.write(0x0000, 0x0A);
+        cpu.cartridge.write(0xA000, 0x42);
+
+        cpu.reset();
+        assert_eq!(cpu.registers(), &initial);
+        assert_eq!(cpu.cartridge.read(0xA000), 0x42, "cartridge ram is kept");
+    }
+
+    #[test]
+    fn test_new_models() {
+        for model in [Model::DMG, Model::MGB, Model::CGB] {
+            let cpu = CPU::new(model, Cartridge::new(CPU_INSTRS_ROM.to_vec())).unwrap();
+            assert_eq!(cpu.model, model);
+        }
+
+        for model in [Model::SGB, Model::SGB2, Model::AGB] {
+            assert_eq!(
+                CPU::new(model, Cartridge::new(CPU_INSTRS_ROM.to_vec())).err(),
+                Some(Error::CPUNotSupported(model))
+            );
+        }
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(CPU::try_new(Model::DMG, Cartridge::new(CPU_INSTRS_ROM.to_vec())).is_ok());
+
+        assert_eq!(
+            CPU::try_new(Model::DMG, Cartridge::new(vec![0x00; 0x100])).err(),
+            Some(Error::Cartridge(CartridgeError::RomTooSmall(0x100)))
+        );
+
+        let rom = vec![0x00; MAX_ROM_SIZE + 1];
+        assert_eq!(
+            CPU::try_new(Model::DMG, Cartridge::new(rom)).err(),
+            Some(Error::Cartridge(CartridgeError::RomTooLarge(
+                MAX_ROM_SIZE + 1
+            )))
+        );
+
+        let mut rom = CPU_INSTRS_ROM.to_vec();
+        rom[0x147] = 0xEE;
+        assert_eq!(
+            CPU::try_new(Model::DMG, Cartridge::new(rom)).err(),
+            Some(Error::Cartridge(CartridgeError::InvalidCartridgeKind(0xEE)))
+        );
+
+        assert_eq!(
+            CPU::try_new(Model::AGB, Cartridge::new(CPU_INSTRS_ROM.to_vec())).err(),
+            Some(Error::CPUNotSupported(Model::AGB))
+        );
+    }
+
+    #[test]
+    fn test_try_new_color_required() {
+        let mut rom = CPU_INSTRS_ROM.to_vec();
+        rom[0x143] = 0xC0;
+
+        for model in [Model::DMG, Model::MGB] {
+            assert_eq!(
+                CPU::try_new(model, Cartridge::new(rom.clone())).err(),
+                Some(Error::ColorRequired(model))
+            );
         }
+        assert!(CPU::try_new(Model::CGB, Cartridge::new(rom)).is_ok());
     }
 }
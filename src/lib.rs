@@ -1,4 +1,12 @@
 pub mod cartridge;
 pub mod cpu;
+pub mod disasm;
+pub mod mbc;
 pub mod metadata;
+pub mod opcodes;
+pub mod patch;
 pub mod registers;
+pub mod testutil;
+pub mod timing;
+
+pub use registers::{Flags, Registers};
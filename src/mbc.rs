@@ -0,0 +1,341 @@
+use crate::cartridge::{Kind, MbcFamily};
+
+// Size of a single switchable ROM bank (and of the fixed bank 0)
+pub const ROM_BANK_SIZE: usize = 0x4000;
+
+// Size of a single switchable external RAM bank
+pub const RAM_BANK_SIZE: usize = 0x2000;
+
+// MBC2 has no external RAM, instead it has 512 half-bytes built in, stored here one per byte
+pub const MBC2_RAM_LEN: usize = 512;
+
+// Memory bank controller, selected from the cartridge kind.
+// https://gbdev.io/pandocs/MBCs.html
+#[derive(Debug, PartialEq, Eq)]
+pub enum Mbc {
+    None,
+    Mbc1(Mbc1),
+    Mbc3(Mbc3),
+}
+
+impl Mbc {
+    pub fn new(kind: &Kind) -> Self {
+        match kind.mbc_family() {
+            MbcFamily::Mbc1 => Mbc::Mbc1(Mbc1::default()),
+            MbcFamily::Mbc3 => Mbc::Mbc3(Mbc3::default()),
+            _ => Mbc::None,
+        }
+    }
+}
+
+// https://gbdev.io/pandocs/MBC1.html
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Mbc1 {
+    // $0000–$1FFF: any value with 0x0A in the lower nibble enables external RAM
+    pub ram_enabled: bool,
+    // $2000–$3FFF: lower 5 bits of the ROM bank number
+    pub bank1: u8,
+    // $4000–$5FFF: 2-bit RAM bank number, or upper 2 bits of the ROM bank number
+    pub bank2: u8,
+    // $6000–$7FFF: banking mode select, false is simple (mode 0) and true is advanced (mode 1)
+    pub advanced: bool,
+}
+
+impl Mbc1 {
+    pub fn write(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.bank1 = value & 0b0001_1111,
+            0x4000..=0x5FFF => self.bank2 = value & 0b0000_0011,
+            0x6000..=0x7FFF => self.advanced = value & 0b0000_0001 != 0,
+            _ => {}
+        }
+    }
+
+    // ROM bank mapped at $4000–$7FFF. Only the 5-bit register is checked for zero, so writing
+    // 0x00 selects bank 1, and banks 0x20/0x40/0x60 end up as 0x21/0x41/0x61.
+    pub fn rom_bank(&self) -> usize {
+        let bank1 = if self.bank1 == 0 { 1 } else { self.bank1 };
+        ((self.bank2 as usize) << 5) | bank1 as usize
+    }
+
+    // ROM bank mapped at $0000–$3FFF. In advanced banking mode the upper bank bits apply here too,
+    // which only matters for ROMs of 1 MiB or more.
+    pub fn zero_bank(&self) -> usize {
+        if self.advanced {
+            (self.bank2 as usize) << 5
+        } else {
+            0
+        }
+    }
+
+    // RAM bank mapped at $A000–$BFFF, only switchable in advanced banking mode.
+    pub fn ram_bank(&self) -> usize {
+        if self.advanced {
+            self.bank2 as usize
+        } else {
+            0
+        }
+    }
+}
+
+// https://gbdev.io/pandocs/MBC3.html
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Mbc3 {
+    // $0000–$1FFF: any value with 0x0A in the lower nibble enables external RAM and the RTC registers
+    pub ram_enabled: bool,
+    // $2000–$3FFF: 7-bit ROM bank number
+    pub bank: u8,
+    // $4000–$5FFF: 0x00-0x03 selects a RAM bank, 0x08-0x0C selects an RTC register
+    pub select: u8,
+    // Last value written to $6000–$7FFF, writing 0x00 then 0x01 latches the clock
+    latch: u8,
+    // The running clock
+    pub rtc: Rtc,
+    // Snapshot of the clock taken on latch, this is what reads observe
+    pub latched: Rtc,
+}
+
+impl Mbc3 {
+    pub fn write(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.bank = value & 0b0111_1111,
+            0x4000..=0x5FFF => self.select = value,
+            0x6000..=0x7FFF => {
+                if self.latch == 0x00 && value == 0x01 {
+                    self.latched = self.rtc;
+                }
+                self.latch = value;
+            }
+            0xA000..=0xBFFF => {
+                if let Some(register) = self.rtc_register() {
+                    if self.ram_enabled {
+                        self.rtc.write(register, value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // ROM bank mapped at $4000–$7FFF, writing 0x00 selects bank 1.
+    pub fn rom_bank(&self) -> usize {
+        if self.bank == 0 {
+            1
+        } else {
+            self.bank as usize
+        }
+    }
+
+    // RAM bank mapped at $A000–$BFFF, if a RAM bank (rather than an RTC register) is selected.
+    pub fn ram_bank(&self) -> Option<usize> {
+        match self.select {
+            0x00..=0x03 => Some(self.select as usize),
+            _ => None,
+        }
+    }
+
+    // RTC register mapped at $A000–$BFFF, if one is selected.
+    pub fn rtc_register(&self) -> Option<u8> {
+        match self.select {
+            0x08..=0x0C => Some(self.select),
+            _ => None,
+        }
+    }
+
+    // Reads the latched RTC register if one is selected and enabled.
+    pub fn read_rtc(&self) -> Option<u8> {
+        match self.rtc_register() {
+            Some(register) if self.ram_enabled => Some(self.latched.read(register)),
+            _ => None,
+        }
+    }
+}
+
+// MBC3 real time clock. It has no notion of wall time, the owner must advance it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Rtc {
+    pub seconds: u8,
+    pub minutes: u8,
+    pub hours: u8,
+    // 9-bit day counter
+    pub days: u16,
+    // Stops the clock while set
+    pub halt: bool,
+    // Set when the day counter overflows past 511, stays set until cleared by a write
+    pub carry: bool,
+}
+
+impl Rtc {
+    // Advances the clock by a number of seconds, unless halted.
+    pub fn advance(&mut self, seconds: u64) {
+        if self.halt {
+            return;
+        }
+
+        let total = self.seconds as u64
+            + self.minutes as u64 * 60
+            + self.hours as u64 * 60 * 60
+            + self.days as u64 * 60 * 60 * 24
+            + seconds;
+
+        let days = total / (60 * 60 * 24);
+        if days > 0x1FF {
+            self.carry = true;
+        }
+
+        self.seconds = (total % 60) as u8;
+        self.minutes = (total / 60 % 60) as u8;
+        self.hours = (total / (60 * 60) % 24) as u8;
+        self.days = (days % 0x200) as u16;
+    }
+
+    pub fn read(&self, register: u8) -> u8 {
+        match register {
+            0x08 => self.seconds,
+            0x09 => self.minutes,
+            0x0A => self.hours,
+            0x0B => self.days as u8,
+            0x0C => {
+                // Bit 0: day counter bit 8, Bit 6: halt, Bit 7: day counter carry
+                (self.days >> 8) as u8 & 0b1 | (self.halt as u8) << 6 | (self.carry as u8) << 7
+            }
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, register: u8, value: u8) {
+        match register {
+            0x08 => self.seconds = value & 0b0011_1111,
+            0x09 => self.minutes = value & 0b0011_1111,
+            0x0A => self.hours = value & 0b0001_1111,
+            0x0B => self.days = self.days & 0x100 | value as u16,
+            0x0C => {
+                self.days = self.days & 0xFF | ((value as u16 & 0b1) << 8);
+                self.halt = value & 0b0100_0000 != 0;
+                self.carry = value & 0b1000_0000 != 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Mbc::new(&Kind::RomOnly), Mbc::None);
+        assert_eq!(Mbc::new(&Kind::Mbc1), Mbc::Mbc1(Mbc1::default()));
+        assert_eq!(Mbc::new(&Kind::Mbc1RamBattery), Mbc::Mbc1(Mbc1::default()));
+    }
+
+    #[test]
+    fn test_mbc1_rom_bank() {
+        let mut mbc = Mbc1::default();
+        assert_eq!(mbc.rom_bank(), 1, "defaults to bank 1");
+
+        mbc.write(0x2000, 0x05);
+        assert_eq!(mbc.rom_bank(), 0x05);
+
+        mbc.write(0x3FFF, 0xE3);
+        assert_eq!(mbc.rom_bank(), 0x03, "upper bits are ignored");
+
+        for (bank2, expected) in [(0, 0x01), (1, 0x21), (2, 0x41), (3, 0x61)] {
+            mbc.write(0x2000, 0x00);
+            mbc.write(0x4000, bank2);
+            assert_eq!(mbc.rom_bank(), expected, "bank {:#04x} quirk", bank2 << 5);
+        }
+    }
+
+    #[test]
+    fn test_mbc1_ram() {
+        let mut mbc = Mbc1::default();
+        mbc.write(0x0000, 0x0A);
+        assert!(mbc.ram_enabled);
+        mbc.write(0x1FFF, 0x1A);
+        assert!(mbc.ram_enabled, "only the lower nibble matters");
+        mbc.write(0x0000, 0x00);
+        assert!(!mbc.ram_enabled);
+
+        mbc.write(0x4000, 0x02);
+        assert_eq!(mbc.ram_bank(), 0, "simple mode is locked to bank 0");
+        assert_eq!(mbc.zero_bank(), 0, "simple mode is locked to bank 0");
+        mbc.write(0x6000, 0x01);
+        assert_eq!(mbc.ram_bank(), 2);
+        assert_eq!(mbc.zero_bank(), 0x40);
+    }
+
+    #[test]
+    fn test_mbc3_banking() {
+        assert_eq!(
+            Mbc::new(&Kind::Mbc3TimerBattery),
+            Mbc::Mbc3(Mbc3::default())
+        );
+
+        let mut mbc = Mbc3::default();
+        assert_eq!(mbc.rom_bank(), 1, "defaults to bank 1");
+        mbc.write(0x2000, 0x00);
+        assert_eq!(mbc.rom_bank(), 1, "bank 0 maps to bank 1");
+        mbc.write(0x2000, 0xFF);
+        assert_eq!(mbc.rom_bank(), 0x7F, "7-bit bank number");
+
+        mbc.write(0x4000, 0x03);
+        assert_eq!(mbc.ram_bank(), Some(3));
+        assert_eq!(mbc.rtc_register(), None);
+        mbc.write(0x4000, 0x0C);
+        assert_eq!(mbc.ram_bank(), None);
+        assert_eq!(mbc.rtc_register(), Some(0x0C));
+    }
+
+    #[test]
+    fn test_mbc3_latch() {
+        let mut mbc = Mbc3::default();
+        mbc.write(0x0000, 0x0A);
+        mbc.write(0x4000, 0x08);
+        mbc.rtc.advance(42);
+        assert_eq!(mbc.read_rtc(), Some(0), "not latched yet");
+
+        mbc.write(0x6000, 0x00);
+        mbc.write(0x6000, 0x01);
+        assert_eq!(mbc.read_rtc(), Some(42));
+
+        mbc.rtc.advance(1);
+        assert_eq!(mbc.read_rtc(), Some(42), "latched value is stable");
+
+        mbc.write(0x6000, 0x01);
+        assert_eq!(mbc.read_rtc(), Some(42), "latch requires 0x00 first");
+
+        mbc.write(0x6000, 0x00);
+        mbc.write(0x6000, 0x01);
+        assert_eq!(mbc.read_rtc(), Some(43));
+
+        mbc.write(0x0000, 0x00);
+        assert_eq!(mbc.read_rtc(), None, "disabled");
+    }
+
+    #[test]
+    fn test_rtc_advance() {
+        let mut rtc = Rtc::default();
+        rtc.advance(60 * 60 * 24 + 60 * 60 * 3 + 60 * 2 + 1);
+        assert_eq!(
+            (rtc.days, rtc.hours, rtc.minutes, rtc.seconds),
+            (1, 3, 2, 1)
+        );
+
+        rtc.days = 0x1FF;
+        rtc.advance(60 * 60 * 24);
+        assert_eq!(rtc.days, 0, "day counter wraps");
+        assert!(rtc.carry, "day counter overflow sets carry");
+        assert_eq!(rtc.read(0x0C), 0b1000_0000);
+
+        rtc.write(0x0C, 0b0100_0001);
+        assert!(rtc.halt);
+        assert!(!rtc.carry, "carry is cleared by writing");
+        assert_eq!(rtc.days, 0x100);
+        rtc.advance(60);
+        assert_eq!(rtc.minutes, 2, "halted clock doesn't advance");
+    }
+}
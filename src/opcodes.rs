@@ -0,0 +1,177 @@
+use crate::disasm::{BASE, CB};
+
+// Static description of an opcode, e.g. for debuggers that need the next PC without executing.
+// Cycle counts are in T-cycles.
+// https://gbdev.io/gb-opcodes/optables/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Info {
+    // Mnemonic with an operand placeholder, shared with the disassembler
+    pub mnemonic: &'static str,
+    // Length in bytes, including the operand
+    pub length: u8,
+    // Cycles taken, or for conditional instructions, cycles when the branch isn't taken.
+    // Illegal opcodes lock up the CPU and report 0.
+    pub cycles: u8,
+    // Cycles when the branch is taken, for conditional JR/JP/CALL/RET
+    pub branch_cycles: Option<u8>,
+}
+
+// Base opcodes. The 0xCB entry describes only the prefix byte, see CB_INFO for what follows it.
+pub const INFO: [Info; 256] = base_info();
+
+// 0xCB-prefixed opcodes. Lengths and cycles include the prefix byte.
+pub const CB_INFO: [Info; 256] = cb_info();
+
+#[rustfmt::skip]
+const BASE_CYCLES: [u8; 256] = [
+//  x0  x1  x2  x3  x4  x5  x6  x7  x8  x9  xA  xB  xC  xD  xE  xF
+     4, 12,  8,  8,  4,  4,  8,  4, 20,  8,  8,  8,  4,  4,  8,  4, // 0x
+     4, 12,  8,  8,  4,  4,  8,  4, 12,  8,  8,  8,  4,  4,  8,  4, // 1x
+     8, 12,  8,  8,  4,  4,  8,  4,  8,  8,  8,  8,  4,  4,  8,  4, // 2x
+     8, 12,  8,  8, 12, 12, 12,  4,  8,  8,  8,  8,  4,  4,  8,  4, // 3x
+     4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4, // 4x
+     4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4, // 5x
+     4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4, // 6x
+     8,  8,  8,  8,  8,  8,  4,  8,  4,  4,  4,  4,  4,  4,  8,  4, // 7x
+     4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4, // 8x
+     4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4, // 9x
+     4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4, // Ax
+     4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4, // Bx
+     8, 12, 12, 16, 12, 16,  8, 16,  8, 16, 12,  4, 12, 24,  8, 16, // Cx
+     8, 12, 12,  0, 12, 16,  8, 16,  8, 16, 12,  0, 12,  0,  8, 16, // Dx
+    12, 12,  8,  0,  0, 16,  8, 16, 16,  4, 16,  0,  0,  0,  8, 16, // Ex
+    12, 12,  8,  4,  0, 16,  8, 16, 12,  8, 16,  4,  0,  0,  8, 16, // Fx
+];
+
+// Cycles for conditional instructions when the branch is taken
+const fn branch_cycles(opcode: u8) -> Option<u8> {
+    match opcode {
+        0x20 | 0x28 | 0x30 | 0x38 => Some(12),
+        0xC0 | 0xC8 | 0xD0 | 0xD8 => Some(20),
+        0xC2 | 0xCA | 0xD2 | 0xDA => Some(16),
+        0xC4 | 0xCC | 0xD4 | 0xDC => Some(24),
+        _ => None,
+    }
+}
+
+// Length of a base opcode from the operand placeholder in its mnemonic, matching disasm::decode.
+const fn length(mnemonic: &str) -> u8 {
+    let bytes = mnemonic.as_bytes();
+    let mut length = 1;
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        match (bytes[i], bytes[i + 1]) {
+            (b'n' | b'a', b'1') => return 3,
+            (b'n' | b'a' | b'e', b'8') => length = 2,
+            _ => {}
+        }
+        i += 1;
+    }
+    length
+}
+
+const fn base_info() -> [Info; 256] {
+    let mut info = [Info {
+        mnemonic: "",
+        length: 0,
+        cycles: 0,
+        branch_cycles: None,
+    }; 256];
+
+    let mut opcode = 0;
+    while opcode < 256 {
+        info[opcode] = Info {
+            mnemonic: BASE[opcode],
+            length: length(BASE[opcode]),
+            cycles: BASE_CYCLES[opcode],
+            branch_cycles: branch_cycles(opcode as u8),
+        };
+        opcode += 1;
+    }
+    info
+}
+
+const fn cb_info() -> [Info; 256] {
+    let mut info = [Info {
+        mnemonic: "",
+        length: 0,
+        cycles: 0,
+        branch_cycles: None,
+    }; 256];
+
+    let mut opcode = 0;
+    while opcode < 256 {
+        // operations on [HL] read and write memory, except BIT which only reads it
+        let cycles = match (opcode & 0x07, opcode) {
+            (0x06, 0x40..=0x7F) => 12,
+            (0x06, _) => 16,
+            _ => 8,
+        };
+        info[opcode] = Info {
+            mnemonic: CB[opcode],
+            length: 2,
+            cycles,
+            branch_cycles: None,
+        };
+        opcode += 1;
+    }
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disasm::decode;
+
+    #[test]
+    fn test_lengths_match_decode() {
+        for opcode in 0..=0xFF_u8 {
+            if opcode != 0xCB {
+                let (_, len) = decode(&[opcode, 0x00, 0x00]);
+                assert_eq!(
+                    INFO[opcode as usize].length as usize, len,
+                    "{:#04x}",
+                    opcode
+                );
+            }
+
+            let (_, len) = decode(&[0xCB, opcode]);
+            assert_eq!(
+                CB_INFO[opcode as usize].length as usize, len,
+                "cb {:#04x}",
+                opcode
+            );
+        }
+        assert_eq!(INFO[0xCB].length, 1, "prefix byte only");
+    }
+
+    #[test]
+    fn test_info() {
+        assert_eq!(
+            INFO[0x01],
+            Info {
+                mnemonic: "LD BC, n16",
+                length: 3,
+                cycles: 12,
+                branch_cycles: None,
+            }
+        );
+        assert_eq!(INFO[0x08].cycles, 20, "LD [a16], SP");
+        assert_eq!(INFO[0x76].mnemonic, "HALT");
+        assert_eq!(INFO[0xE8].length, 2, "ADD SP, e8");
+        assert_eq!(INFO[0xD3].cycles, 0, "illegal");
+
+        for (opcode, cycles, taken) in
+            [(0x20, 8, 12), (0xC2, 12, 16), (0xC4, 12, 24), (0xC0, 8, 20)]
+        {
+            assert_eq!(INFO[opcode].cycles, cycles, "{:#04x}", opcode);
+            assert_eq!(INFO[opcode].branch_cycles, Some(taken), "{:#04x}", opcode);
+        }
+        assert_eq!(INFO[0xC3].branch_cycles, None, "unconditional JP");
+
+        assert_eq!(CB_INFO[0x00].mnemonic, "RLC B");
+        assert_eq!(CB_INFO[0x00].cycles, 8);
+        assert_eq!(CB_INFO[0x46].cycles, 12, "BIT 0, [HL]");
+        assert_eq!(CB_INFO[0x86].cycles, 16, "RES 0, [HL]");
+    }
+}
@@ -0,0 +1,204 @@
+use crate::cartridge::{MAX_ROM_SIZE, MIN_ROM_SIZE};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PatchError {
+    #[error("invalid ips header")]
+    InvalidHeader,
+    #[error("truncated ips patch at offset: {0:#x}")]
+    Truncated(usize),
+    #[error("patched rom too large: {0} bytes (max {MAX_ROM_SIZE})")]
+    TooLarge(usize),
+    #[error("patched rom too small: {0} bytes (min {MIN_ROM_SIZE})")]
+    TooSmall(usize),
+}
+
+// A single IPS record. RLE records are kept as a run so a tiny patch can't balloon in memory
+// before the size check.
+enum Record<'a> {
+    Data { offset: usize, data: &'a [u8] },
+    Rle { offset: usize, run: usize, byte: u8 },
+}
+
+impl Record<'_> {
+    fn range(&self) -> std::ops::Range<usize> {
+        match *self {
+            Record::Data { offset, data } => offset..offset + data.len(),
+            Record::Rle { offset, run, .. } => offset..offset + run,
+        }
+    }
+}
+
+// Cursor over the raw patch bytes, erroring with the current position when it runs out
+struct Reader<'a> {
+    patch: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], PatchError> {
+        let bytes = self
+            .patch
+            .get(self.pos..self.pos + n)
+            .ok_or(PatchError::Truncated(self.pos))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<usize, PatchError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+    }
+
+    fn u24(&mut self) -> Result<usize, PatchError> {
+        let bytes = self.take(3)?;
+        Ok(u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]) as usize)
+    }
+}
+
+// Applies an IPS patch to a ROM. The whole patch is parsed before anything is written, so a malformed
+// patch leaves the ROM untouched. Records past the end of the ROM grow it, and the optional truncation
+// extension after the "EOF" marker shrinks it, as long as the result still holds a whole header.
+// https://zerosoft.zophar.net/ips.php
+pub fn apply_ips(rom: &mut Vec<u8>, patch: &[u8]) -> Result<(), PatchError> {
+    const HEADER: &[u8] = b"PATCH";
+    const EOF: usize = 0x454F46;
+
+    if !patch.starts_with(HEADER) {
+        return Err(PatchError::InvalidHeader);
+    }
+
+    let mut reader = Reader {
+        patch,
+        pos: HEADER.len(),
+    };
+    let mut records = vec![];
+    let mut len = rom.len();
+
+    loop {
+        let offset = reader.u24()?;
+        if offset == EOF {
+            break;
+        }
+
+        let record = match reader.u16()? {
+            // size 0 is an RLE record: 2-byte run length then the byte to repeat
+            0 => Record::Rle {
+                offset,
+                run: reader.u16()?,
+                byte: reader.take(1)?[0],
+            },
+            size => Record::Data {
+                offset,
+                data: reader.take(size)?,
+            },
+        };
+
+        len = len.max(record.range().end);
+        records.push(record);
+    }
+
+    if reader.pos + 3 <= patch.len() {
+        len = reader.u24()?;
+    }
+
+    if len > MAX_ROM_SIZE {
+        return Err(PatchError::TooLarge(len));
+    }
+
+    if len < MIN_ROM_SIZE {
+        return Err(PatchError::TooSmall(len));
+    }
+
+    for record in records {
+        let range = record.range();
+        if range.end > rom.len() {
+            rom.resize(range.end, 0x00);
+        }
+        match record {
+            Record::Data { data, .. } => rom[range].copy_from_slice(data),
+            Record::Rle { byte, .. } => rom[range].fill(byte),
+        }
+    }
+
+    rom.resize(len, 0x00);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_ips() {
+        let mut rom = vec![0x00; MIN_ROM_SIZE];
+        let patch = [
+            b"PATCH".as_slice(),
+            &[0x00, 0x00, 0x01, 0x00, 0x02, 0xAA, 0xBB],
+            &[0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x02, 0xCC],
+            b"EOF",
+        ]
+        .concat();
+
+        apply_ips(&mut rom, &patch).unwrap();
+        assert_eq!(rom[..8], [0x00, 0xAA, 0xBB, 0x00, 0x00, 0xCC, 0xCC, 0x00]);
+        assert_eq!(rom.len(), MIN_ROM_SIZE);
+    }
+
+    #[test]
+    fn test_apply_ips_resize() {
+        let mut rom = vec![0x00; MIN_ROM_SIZE];
+        let patch = [
+            b"PATCH".as_slice(),
+            &[0x00, 0x01, 0x51, 0x00, 0x01, 0xFF],
+            b"EOF",
+        ]
+        .concat();
+        apply_ips(&mut rom, &patch).unwrap();
+        assert_eq!(rom.len(), 0x152, "grows");
+        assert_eq!(rom[0x150..], [0x00, 0xFF]);
+
+        let patch = [b"PATCH".as_slice(), b"EOF", &[0x00, 0x01, 0x50]].concat();
+        apply_ips(&mut rom, &patch).unwrap();
+        assert_eq!(rom.len(), MIN_ROM_SIZE, "truncates");
+    }
+
+    #[test]
+    fn test_apply_ips_errors() {
+        let mut rom = vec![0x00; MIN_ROM_SIZE];
+        assert_eq!(apply_ips(&mut rom, b"PAT"), Err(PatchError::InvalidHeader));
+
+        let patch = [b"PATCH".as_slice(), &[0x00, 0x00, 0x01, 0x00, 0x04, 0xAA]].concat();
+        assert_eq!(apply_ips(&mut rom, &patch), Err(PatchError::Truncated(10)));
+        assert_eq!(rom, vec![0x00; MIN_ROM_SIZE], "untouched on error");
+
+        let patch = [b"PATCH".as_slice(), &[0x00, 0x00, 0x01, 0x00, 0x01, 0xAA]].concat();
+        assert_eq!(
+            apply_ips(&mut rom, &patch),
+            Err(PatchError::Truncated(11)),
+            "missing EOF"
+        );
+
+        let patch = [b"PATCH".as_slice(), b"EOF", &[0x00, 0x00, 0x10]].concat();
+        assert_eq!(apply_ips(&mut rom, &patch), Err(PatchError::TooSmall(0x10)));
+        assert_eq!(rom.len(), MIN_ROM_SIZE, "untouched on error");
+    }
+
+    #[test]
+    fn test_apply_ips_rle_too_large() {
+        // a handful of 8-byte RLE records each claiming a 64 KiB run past the 8 MiB limit
+        let mut patch = b"PATCH".to_vec();
+        for offset in [0x7F_0000u32, 0x7F_8000, 0x7F_FF00] {
+            patch.extend_from_slice(&offset.to_be_bytes()[1..]);
+            patch.extend_from_slice(&[0x00, 0x00, 0xFF, 0xFF, 0xAA]);
+        }
+        patch.extend_from_slice(b"EOF");
+
+        let mut rom = vec![0x00; MIN_ROM_SIZE];
+        assert_eq!(
+            apply_ips(&mut rom, &patch),
+            Err(PatchError::TooLarge(0x7F_FF00 + 0xFFFF))
+        );
+        assert_eq!(rom.len(), MIN_ROM_SIZE, "untouched on error");
+    }
+}
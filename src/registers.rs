@@ -1,6 +1,5 @@
 use crate::cartridge::Cartridge;
 use crate::cpu::Model;
-use std::vec;
 
 #[derive(Debug, Clone, Copy)]
 pub enum Flags {
@@ -40,7 +39,7 @@ pub struct Registers {
 
 impl std::fmt::Debug for Registers {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let flags: String = vec![Flags::Z, Flags::N, Flags::H, Flags::C]
+        let flags: String = [Flags::Z, Flags::N, Flags::H, Flags::C]
             .iter()
             .map(|flag| {
                 if self.get_flag(*flag) {
@@ -67,36 +66,45 @@ impl std::fmt::Debug for Registers {
 }
 
 impl Registers {
+    // CGB values are for CGB mode, the DMG compatibility mode values depend on the boot ROM's
+    // title checksum and aren't modelled.
     pub fn new(model: Model, cart: &Cartridge) -> Registers {
-        if model != Model::DMG {
-            panic!("model not supported: {:?}", model);
-        }
-
         // https://gbdev.io/pandocs/Power_Up_Sequence.html#cpu-registers
-        let mut registers = Registers {
-            a: 0x01,
-            f: 0x00,
-            b: 0x00,
-            c: 0x13,
-            d: 0x00,
-            e: 0xD8,
-            h: 0x01,
-            l: 0x4D,
-            pc: 0x0100,
-            sp: 0xFFFE,
+        let mut registers = match model {
+            Model::DMG | Model::MGB => Registers {
+                // the MGB boot rom is identical besides A
+                a: if model == Model::MGB { 0xFF } else { 0x01 },
+                f: 0x00,
+                b: 0x00,
+                c: 0x13,
+                d: 0x00,
+                e: 0xD8,
+                h: 0x01,
+                l: 0x4D,
+                pc: 0x0100,
+                sp: 0xFFFE,
+            },
+            Model::CGB => Registers {
+                a: 0x11,
+                f: 0x00,
+                b: 0x00,
+                c: 0x00,
+                d: 0xFF,
+                e: 0x56,
+                h: 0x00,
+                l: 0x0D,
+                pc: 0x0100,
+                sp: 0xFFFE,
+            },
+            _ => panic!("model not supported: {:?}", model),
         };
 
         registers.set_flag(Flags::Z, true);
 
-        match model {
-            Model::DMG | Model::MGB => {
-                // for DMG and MGB, the carry and half-carry flags are set if the checksum != 0
-                if cart.header_checksum() != 0x00 {
-                    registers.set_flag(Flags::H, true);
-                    registers.set_flag(Flags::C, true);
-                }
-            }
-            _ => {}
+        // for DMG and MGB, the carry and half-carry flags are set if the checksum != 0
+        if matches!(model, Model::DMG | Model::MGB) && cart.header_checksum() != 0x00 {
+            registers.set_flag(Flags::H, true);
+            registers.set_flag(Flags::C, true);
         }
 
         registers
@@ -108,7 +116,7 @@ impl Registers {
 
     pub fn set_af(&mut self, value: u16) {
         self.a = (value >> 8) as u8;
-        // TODO(robherley): figure out if masking is necessary
+        // the lower nibble of F doesn't exist on hardware and always reads as 0 (e.g. after POP AF)
         self.f = value as u8 & 0xF0;
     }
 
@@ -139,6 +147,24 @@ impl Registers {
         self.l = value as u8;
     }
 
+    // Moves PC forward by n bytes, wrapping past 0xFFFF like the hardware does.
+    pub fn advance_pc(&mut self, n: u16) {
+        self.pc = self.pc.wrapping_add(n);
+    }
+
+    // Makes room for a 16-bit value on the stack, returning the new SP to write it at.
+    pub fn push_sp(&mut self) -> u16 {
+        self.sp = self.sp.wrapping_sub(2);
+        self.sp
+    }
+
+    // Releases a 16-bit value from the stack, returning the old SP to read it from.
+    pub fn pop_sp(&mut self) -> u16 {
+        let sp = self.sp;
+        self.sp = self.sp.wrapping_add(2);
+        sp
+    }
+
     pub fn get_flag(&self, flag: Flags) -> bool {
         self.f & flag as u8 != 0
     }
@@ -179,4 +205,107 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_init_mgb() {
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        let registers = Registers::new(Model::MGB, &cart);
+
+        assert_eq!(
+            registers,
+            Registers {
+                a: 0xFF,
+                f: 0xB0,
+                b: 0x00,
+                c: 0x13,
+                d: 0x00,
+                e: 0xD8,
+                h: 0x01,
+                l: 0x4D,
+                pc: 0x0100,
+                sp: 0xFFFE,
+            }
+        );
+    }
+
+    #[test]
+    fn test_init_cgb() {
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        let registers = Registers::new(Model::CGB, &cart);
+
+        assert_eq!(
+            registers,
+            Registers {
+                a: 0x11,
+                f: 0x80,
+                b: 0x00,
+                c: 0x00,
+                d: 0xFF,
+                e: 0x56,
+                h: 0x00,
+                l: 0x0D,
+                pc: 0x0100,
+                sp: 0xFFFE,
+            }
+        );
+    }
+
+    #[test]
+    fn test_init_zero_checksum() {
+        let cart = Cartridge::new(vec![0x00; 65536]);
+        assert_eq!(Registers::new(Model::DMG, &cart).f, 0x80, "only Z is set");
+        assert_eq!(Registers::new(Model::MGB, &cart).f, 0x80, "only Z is set");
+    }
+
+    #[test]
+    fn test_set_af_masks_f() {
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        let mut registers = Registers::new(Model::DMG, &cart);
+
+        for value in [0xFF0F, 0x12FF, 0x0001] {
+            registers.set_af(value);
+            assert_eq!(registers.f & 0x0F, 0, "low nibble of {:#06X}", value);
+            assert_eq!(registers.af(), value & 0xFFF0);
+        }
+
+        registers.set_flag(Flags::C, true);
+        registers.set_flag(Flags::Z, true);
+        assert_eq!(registers.f & 0x0F, 0, "flags only touch the high nibble");
+    }
+
+    #[test]
+    fn test_advance_pc() {
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        let mut registers = Registers::new(Model::DMG, &cart);
+
+        registers.advance_pc(3);
+        assert_eq!(registers.pc, 0x0103);
+
+        registers.pc = 0xFFFF;
+        registers.advance_pc(1);
+        assert_eq!(registers.pc, 0x0000, "wraps at the top of memory");
+
+        registers.pc = 0xFFFE;
+        registers.advance_pc(3);
+        assert_eq!(registers.pc, 0x0001);
+    }
+
+    #[test]
+    fn test_push_pop_sp() {
+        let cart = Cartridge::new(CPU_INSTRS_ROM.to_vec());
+        let mut registers = Registers::new(Model::DMG, &cart);
+
+        assert_eq!(registers.push_sp(), 0xFFFC);
+        assert_eq!(registers.pop_sp(), 0xFFFC);
+        assert_eq!(registers.sp, 0xFFFE);
+
+        registers.sp = 0x0001;
+        assert_eq!(registers.push_sp(), 0xFFFF, "wraps below 0x0000");
+        assert_eq!(registers.pop_sp(), 0xFFFF);
+        assert_eq!(registers.sp, 0x0001, "wraps past 0xFFFF");
+
+        registers.sp = 0xFFFF;
+        assert_eq!(registers.pop_sp(), 0xFFFF);
+        assert_eq!(registers.sp, 0x0001);
+    }
 }
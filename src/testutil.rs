@@ -0,0 +1,45 @@
+// Finds the first character where two serial logs diverge, as (index, expected, actual).
+// If one log is a prefix of the other, the missing side is reported as '\0'.
+// Returns None when the logs are identical.
+pub fn diff_serial(expected: &str, actual: &str) -> Option<(usize, char, char)> {
+    let mut expected = expected.chars();
+    let mut actual = actual.chars();
+    let mut index = 0;
+
+    loop {
+        match (expected.next(), actual.next()) {
+            (None, None) => return None,
+            (e, a) if e != a => return Some((index, e.unwrap_or('\0'), a.unwrap_or('\0'))),
+            _ => index += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_serial() {
+        assert_eq!(diff_serial("", ""), None);
+        assert_eq!(
+            diff_serial("cpu_instrs\n\nPassed", "cpu_instrs\n\nPassed"),
+            None
+        );
+        assert_eq!(
+            diff_serial("01:ok  02:ok", "01:ok  02:01"),
+            Some((10, 'o', '0')),
+            "first mismatch"
+        );
+        assert_eq!(
+            diff_serial("Passed", "Pass"),
+            Some((4, 'e', '\0')),
+            "truncated"
+        );
+        assert_eq!(
+            diff_serial("Pass", "Passed"),
+            Some((4, '\0', 'e')),
+            "extra output"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::ops::{Add, AddAssign};
+
+// The DMG master clock runs at 4.194304 MHz. Each tick of it is a "t-cycle".
+pub const CLOCK_HZ: u32 = 4_194_304;
+
+// Number of t-cycles in a single m-cycle.
+pub const T_PER_M: u64 = 4;
+
+// T-cycles ("ticks") are the 4.19 MHz master clock ticks. Unless otherwise noted,
+// cycle counts across the public API are t-cycles.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TCycles(pub u64);
+
+// M-cycles ("machine cycles") are the unit the SM83 executes memory accesses in. One m-cycle is
+// four t-cycles, so instruction timing tables listing 1/2/3/... cycles are usually m-cycles.
+// There's no From<TCycles> since it would drop partial m-cycles, use TCycles::to_m instead.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MCycles(pub u64);
+
+impl TCycles {
+    // Converts to m-cycles, truncating any partial m-cycle.
+    pub fn to_m(self) -> MCycles {
+        MCycles(self.0 / T_PER_M)
+    }
+}
+
+impl MCycles {
+    pub fn to_t(self) -> TCycles {
+        TCycles(self.0 * T_PER_M)
+    }
+}
+
+impl From<MCycles> for TCycles {
+    fn from(value: MCycles) -> Self {
+        value.to_t()
+    }
+}
+
+impl Add for TCycles {
+    type Output = TCycles;
+
+    fn add(self, rhs: TCycles) -> TCycles {
+        TCycles(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for TCycles {
+    fn add_assign(&mut self, rhs: TCycles) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Add for MCycles {
+    type Output = MCycles;
+
+    fn add(self, rhs: MCycles) -> MCycles {
+        MCycles(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for MCycles {
+    fn add_assign(&mut self, rhs: MCycles) {
+        self.0 += rhs.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(MCycles(1).to_t(), TCycles(4));
+        assert_eq!(TCycles(4).to_m(), MCycles(1));
+        assert_eq!(TCycles(7).to_m(), MCycles(1), "truncates partial m-cycles");
+        assert_eq!(TCycles::from(MCycles(6)), TCycles(24));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for m in [0, 1, 2, 5, 17_556] {
+            assert_eq!(MCycles(m).to_t().to_m(), MCycles(m));
+        }
+    }
+
+    #[test]
+    fn test_add() {
+        let mut t = TCycles(4) + TCycles(8);
+        t += MCycles(1).to_t();
+        assert_eq!(t, TCycles(16));
+
+        let mut m = MCycles(1) + MCycles(2);
+        m += MCycles(1);
+        assert_eq!(m, MCycles(4));
+    }
+}